@@ -0,0 +1,168 @@
+use crate::http::backoff::Backoff;
+use crate::http::notify::Notify;
+use crate::screens::main::notify_viewer::NvMsg;
+use crate::storage::Store;
+use reqwest::Client;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::error;
+
+/// `Action::DeleteNotify` 真正的处理过程：依次删除勾选的通知，风控退避和
+/// 联动删除评论都交给 `Notify::remove` 处理，断点续删的 id 过滤已由 `NotifyViewer`
+/// 在构造 `Action::DeleteNotify` 前用 `retain` 做过一遍，这里只管
+/// 调度节奏、把每一步的结果收成 `NvMsg`，以及在 `stop` 置位
+/// （`NvMsg::StopDeleteNotify` -> `ChannelMsg::StopDeleteComment` 到达）时提前收尾。
+///
+/// 返回值是这一整轮要喂回 `NotifyViewer::update` 的消息序列，最后一条固定是
+/// `NvMsg::AllNotifyDeleted`。调用方（`crate::main`）负责按顺序把它们转发过去。
+pub async fn run_delete_loop(
+    cl: Arc<Client>,
+    csrf: Arc<String>,
+    notify: Arc<Mutex<HashMap<u64, Notify>>>,
+    store: Option<Arc<Mutex<Store>>>,
+    backoff: Arc<Mutex<Backoff>>,
+    cascade_delete: bool,
+    stop: Arc<AtomicBool>,
+) -> Vec<NvMsg> {
+    let mut events = Vec::new();
+
+    let Some(store) = store else {
+        error!("Delete loop started without a cache store; nothing to delete against");
+        events.push(NvMsg::AllNotifyDeleted {
+            removed: 0,
+            failed: 0,
+        });
+        return events;
+    };
+
+    let ids: Vec<u64> = {
+        let guard = notify.lock().await;
+        guard
+            .iter()
+            .filter(|(_, n)| n.is_selected)
+            .map(|(id, _)| *id)
+            .collect()
+    };
+
+    let mut removed = 0u64;
+    let mut failed = 0u64;
+    for id in ids {
+        if stop.load(Ordering::Relaxed) {
+            break;
+        }
+        let target = {
+            let guard = notify.lock().await;
+            guard.get(&id).cloned()
+        };
+        let Some(target) = target else { continue };
+
+        match target
+            .remove(
+                id,
+                cl.clone(),
+                csrf.clone(),
+                store.clone(),
+                backoff.clone(),
+                cascade_delete,
+            )
+            .await
+        {
+            Ok(removed_ids) => {
+                removed += removed_ids.len() as u64;
+                events.push(NvMsg::NotifyDeleted { ids: removed_ids });
+            }
+            Err(e) => {
+                error!("Failed to remove notify {id}: {e}");
+                failed += 1;
+            }
+        }
+
+        let delay = backoff.lock().await.current_delay();
+        events.push(NvMsg::BackoffChanged(delay));
+        if stop.load(Ordering::Relaxed) {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_secs_f32(delay)).await;
+    }
+
+    events.push(NvMsg::AllNotifyDeleted { removed, failed });
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `NvMsg` carries non-`PartialEq` payloads (e.g. `NotifysFetched`'s boxed error), so
+    /// assert on shape instead of deriving/using `assert_eq!` on the whole enum.
+    fn assert_only_all_notify_deleted(events: &[NvMsg], expected_removed: u64, expected_failed: u64) {
+        assert_eq!(events.len(), 1);
+        match &events[0] {
+            NvMsg::AllNotifyDeleted { removed, failed } => {
+                assert_eq!(*removed, expected_removed);
+                assert_eq!(*failed, expected_failed);
+            }
+            other => panic!("expected AllNotifyDeleted, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn missing_store_short_circuits_with_zero_summary() {
+        let events = run_delete_loop(
+            Arc::new(Client::new()),
+            Arc::new(String::new()),
+            Arc::new(Mutex::new(HashMap::new())),
+            None,
+            Arc::new(Mutex::new(Backoff::new(3.0))),
+            false,
+            Arc::new(AtomicBool::new(false)),
+        )
+        .await;
+
+        assert_only_all_notify_deleted(&events, 0, 0);
+    }
+
+    #[tokio::test]
+    async fn no_selected_notifies_finishes_immediately_without_any_network_call() {
+        let mut h = HashMap::new();
+        h.insert(1, Notify::new(1, "unselected".to_string(), None));
+        let store = Arc::new(Mutex::new(Store::open(":memory:").unwrap()));
+
+        let events = run_delete_loop(
+            Arc::new(Client::new()),
+            Arc::new(String::new()),
+            Arc::new(Mutex::new(h)),
+            Some(store),
+            Arc::new(Mutex::new(Backoff::new(3.0))),
+            false,
+            Arc::new(AtomicBool::new(false)),
+        )
+        .await;
+
+        assert_only_all_notify_deleted(&events, 0, 0);
+    }
+
+    #[tokio::test]
+    async fn stop_flag_set_before_start_skips_every_selected_id() {
+        let mut h = HashMap::new();
+        let mut n = Notify::new(1, "selected".to_string(), None);
+        n.is_selected = true;
+        h.insert(1, n);
+        let store = Arc::new(Mutex::new(Store::open(":memory:").unwrap()));
+
+        let events = run_delete_loop(
+            Arc::new(Client::new()),
+            Arc::new(String::new()),
+            Arc::new(Mutex::new(h)),
+            Some(store),
+            Arc::new(Mutex::new(Backoff::new(3.0))),
+            false,
+            Arc::new(AtomicBool::new(true)),
+        )
+        .await;
+
+        assert_only_all_notify_deleted(&events, 0, 0);
+    }
+}
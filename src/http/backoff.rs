@@ -0,0 +1,74 @@
+/// 自适应限速调度器：批量删除时遇到风控响应码就把间隔翻倍（直到上限），
+/// 持续成功则让间隔慢慢衰减回用户在 UI 里设置的 `sleep_seconds`。
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    base: f32,
+    cap: f32,
+    current: f32,
+}
+
+/// Bilibili 接口常见的风控响应码。
+pub fn is_rate_limited_code(code: i64) -> bool {
+    matches!(code, -412 | -509)
+}
+
+impl Backoff {
+    pub fn new(base_seconds: f32) -> Self {
+        let base = base_seconds.max(0.0);
+        Backoff {
+            base,
+            cap: (base * 16.0).max(30.0),
+            current: base,
+        }
+    }
+
+    /// 命中风控：间隔翻倍，不超过上限。
+    pub fn note_rate_limited(&mut self) {
+        self.current = (self.current * 2.0).min(self.cap).max(self.base.max(1.0));
+    }
+
+    /// 正常成功：缓慢衰减回基础间隔。
+    pub fn note_success(&mut self) {
+        self.current = (self.current * 0.9).max(self.base);
+    }
+
+    pub fn current_delay(&self) -> f32 {
+        self.current
+    }
+
+    pub fn is_backing_off(&self) -> bool {
+        self.current > self.base
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decay_never_undercuts_base() {
+        let mut b = Backoff::new(3.0);
+        b.note_rate_limited();
+        for _ in 0..50 {
+            b.note_success();
+        }
+        assert_eq!(b.current_delay(), 3.0);
+    }
+
+    #[test]
+    fn repeated_rate_limits_saturate_at_cap() {
+        let mut b = Backoff::new(3.0);
+        for _ in 0..50 {
+            b.note_rate_limited();
+        }
+        assert_eq!(b.current_delay(), 48.0); // (3.0 * 16.0).max(30.0)
+    }
+
+    #[test]
+    fn zero_base_rate_limit_does_not_get_stuck_at_zero() {
+        let mut b = Backoff::new(0.0);
+        b.note_rate_limited();
+        assert!(b.current_delay() >= 1.0);
+        assert!(b.is_backing_off());
+    }
+}
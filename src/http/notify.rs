@@ -1,36 +1,172 @@
+use super::backoff::{is_rate_limited_code, Backoff};
 use super::utility::get_json;
+use crate::storage::Store;
 use crate::types::Result;
 use reqwest::{Client, Url};
 use serde_json::{json, Value};
 use std::collections::HashMap;
 use std::sync::Arc;
+use tokio::sync::Mutex;
 use tokio::try_join;
 use tracing::{error, info, instrument};
 
+/// 从一条通知的原始 json 里取出展示用的文本，各 feed 的字段名不完全一致，按常见字段依次尝试。
+/// `title` 在 reply/at 的 item 里经常是存在但为空字符串，所以要按"非空"而不是"存在"来决定
+/// 要不要继续往下一个字段找，否则大多数 reply/at 通知都会被取成空字符串。
+fn extract_content(i: &Value) -> String {
+    i["item"]["title"]
+        .as_str()
+        .filter(|s| !s.is_empty())
+        .or_else(|| i["item"]["source_content"].as_str().filter(|s| !s.is_empty()))
+        .or_else(|| i["item"]["content"].as_str().filter(|s| !s.is_empty()))
+        .unwrap_or("")
+        .to_string()
+}
+
+/// 系统通知的原始 json 结构和普通 msgfeed 不同，展示文本直接在顶层。同样要按非空过滤，
+/// 理由见 `extract_content`。
+fn extract_system_content(i: &Value) -> String {
+    i["title"]
+        .as_str()
+        .filter(|s| !s.is_empty())
+        .or_else(|| i["text"].as_str().filter(|s| !s.is_empty()))
+        .unwrap_or("")
+        .to_string()
+}
+
+/// 评论/At 通知指向的那条评论的坐标，用来联动删除评论本体（见 `Notify::remove` 里的 `cascade_delete_comment`）。
+#[derive(Debug, Clone, Copy)]
+pub struct CommentTarget {
+    pub oid: u64,
+    pub type_: u8,
+    pub rpid: u64,
+}
+
+/// 从 reply/at 通知的原始 item 里取出其指向的评论坐标，缺任何一个字段都视为取不到。
+fn extract_comment_target(i: &Value) -> Option<CommentTarget> {
+    let oid = i["item"]["oid"].as_u64()?;
+    let type_ = i["item"]["type"]
+        .as_u64()
+        .or_else(|| i["item"]["natural_type"].as_u64())? as u8;
+    let rpid = i["item"]["rpid"].as_u64()?;
+    Some(CommentTarget { oid, type_, rpid })
+}
+
+/// 描述一个 cursor 分页的 msgfeed（赞/评论/At），把三处几乎相同的抓取循环收敛成一个驱动函数，
+/// 新增一个 feed（比如私信）只需要在这里加一个 `NotifySource`。
+struct NotifySource {
+    tp: u8,
+    first_url: &'static str,
+    /// 分页游标对应的时间字段名，例如 `like_time` / `reply_time` / `at_time`
+    time_key: &'static str,
+    /// 定位 `items` 数组和 `cursor` 对象的共同前缀，例如 `data.total` 或 `data`
+    base_pointer: fn(&Value) -> &Value,
+    /// 仅 reply/at 这两个 feed 的 item 里带有可联动删除的评论坐标
+    target_extractor: Option<fn(&Value) -> Option<CommentTarget>>,
+    empty_message: &'static str,
+    done_message: &'static str,
+}
+
+impl NotifySource {
+    const LIKE: Self = NotifySource {
+        tp: 0,
+        first_url: "https://api.bilibili.com/x/msgfeed/like?platform=web&build=0&mobi_app=web",
+        time_key: "like_time",
+        base_pointer: |json| &json["data"]["total"],
+        target_extractor: None,
+        empty_message: "没有收到赞的通知。",
+        done_message: "收到赞的通知处理完毕",
+    };
+    const REPLY: Self = NotifySource {
+        tp: 1,
+        first_url: "https://api.bilibili.com/x/msgfeed/reply?platform=web&build=0&mobi_app=web",
+        time_key: "reply_time",
+        base_pointer: |json| &json["data"],
+        target_extractor: Some(extract_comment_target),
+        empty_message: "没有收到评论的通知。",
+        done_message: "收到评论的通知处理完毕",
+    };
+    const AT: Self = NotifySource {
+        tp: 2,
+        first_url: "https://api.bilibili.com/x/msgfeed/at?build=0&mobi_app=web",
+        time_key: "at_time",
+        base_pointer: |json| &json["data"],
+        target_extractor: Some(extract_comment_target),
+        empty_message: "没有被At的通知。",
+        done_message: "被At的通知处理完毕",
+    };
+}
+
+#[derive(Debug, Clone)]
 pub struct Notify {
     tp: u8,
-    is_selected: bool,
+    pub(crate) is_selected: bool,
+    pub(crate) content: String,
     /// 删除系统通知的两种api
     system_notify_api: Option<u8>,
+    /// reply/at 通知指向的评论坐标，删除通知时可以联动删除这条评论
+    target: Option<CommentTarget>,
 }
 impl Notify {
-    pub fn new(tp: u8) -> Notify {
+    pub fn new(tp: u8, content: String, target: Option<CommentTarget>) -> Notify {
         Notify {
             tp,
             is_selected: false,
+            content,
             system_notify_api: None,
+            target,
         }
     }
 
-    fn new_system_notify(tp: u8, api_type: u8) -> Notify {
+    fn new_system_notify(tp: u8, api_type: u8, content: String) -> Notify {
         Notify {
             tp,
             is_selected: false,
+            content,
             system_notify_api: Some(api_type),
+            target: None,
         }
     }
+
+    /// 从本地 SQLite 缓存（见 `crate::storage::Store`）重建的通知，不经过网络抓取。
+    pub(crate) fn from_cached(
+        tp: u8,
+        system_notify_api: Option<u8>,
+        content: String,
+        target: Option<CommentTarget>,
+    ) -> Notify {
+        Notify {
+            tp,
+            is_selected: false,
+            content,
+            system_notify_api,
+            target,
+        }
+    }
+
+    pub(crate) fn target(&self) -> Option<CommentTarget> {
+        self.target
+    }
+
+    pub(crate) fn tp(&self) -> u8 {
+        self.tp
+    }
+
+    pub(crate) fn system_notify_api(&self) -> Option<u8> {
+        self.system_notify_api
+    }
+    /// 删除这条通知。返回所有因此从缓存/列表里摘掉的通知 id：通常只有 `id` 自己，
+    /// 但开启联动删除评论后，同一条评论上的另一条 reply/at 通知也会被一并摘掉，一起报给调用方。
     #[instrument(skip_all)]
-    pub async fn remove(&self, id: u64, cl: Arc<Client>, csrf: Arc<String>) -> Result<u64> {
+    pub async fn remove(
+        &self,
+        id: u64,
+        cl: Arc<Client>,
+        csrf: Arc<String>,
+        store: Arc<Mutex<Store>>,
+        backoff: Arc<Mutex<Backoff>>,
+        cascade_delete_comment: bool,
+    ) -> Result<Vec<u64>> {
         match self.system_notify_api {
             Some(api_type) => {
                 let json = if api_type == 0 {
@@ -49,9 +185,16 @@ impl Notify {
                     .json()
                     .await
                     ?;
-                if json_res["code"].as_i64().unwrap() == 0 {
+                let code = json_res["code"].as_i64().unwrap_or(-1);
+                store.lock().await.record_deletion(id, self.tp, code)?;
+                if is_rate_limited_code(code) {
+                    backoff.lock().await.note_rate_limited();
+                } else if code == 0 {
+                    backoff.lock().await.note_success();
+                }
+                if code == 0 {
                     info!("Remove system notify {id} successfully");
-                    Ok(id)
+                    Ok(vec![id])
                 } else {
                     let e = format!(
                         "Can't remove the system notify. Response json: {}",
@@ -80,13 +223,66 @@ impl Notify {
                     .error_for_status()?
                     .json()
                     .await?;
-                if json_res["code"]
+                let code = json_res["code"]
                     .as_i64()
-                    .ok_or("Remove Notify: Parse json res code failed")?
-                    == 0
-                {
+                    .ok_or("Remove Notify: Parse json res code failed")?;
+                store.lock().await.record_deletion(id, self.tp, code)?;
+                if is_rate_limited_code(code) {
+                    backoff.lock().await.note_rate_limited();
+                } else if code == 0 {
+                    backoff.lock().await.note_success();
+                }
+                if code == 0 {
                     info!("Remove notify {} successfully", id);
-                    Ok(id)
+                    let mut removed_ids = vec![id];
+                    // 评论/At 类通知还指向着一条评论，开启联动删除时把评论本体也删掉
+                    if cascade_delete_comment && matches!(self.tp, 1 | 2) {
+                        if let Some(target) = self.target {
+                            // target 是从 msgfeed 的 item 字段猜出来的坐标（见 extract_comment_target），
+                            // 打一条日志留痕，方便核对猜测是否和评论清理页实际删掉的评论对得上。
+                            info!(
+                                "Cascade deleting comment oid={} type_={} rpid={} for notify {id}",
+                                target.oid, target.type_, target.rpid
+                            );
+                            match crate::http::comment::remove(
+                                cl.clone(),
+                                csrf.clone(),
+                                target.oid,
+                                target.type_,
+                                target.rpid,
+                            )
+                            .await
+                            {
+                                Ok(()) => {
+                                    // 同一条评论可能同时挂着 reply 和 at 两种通知，
+                                    // 评论本体没了之后把另一条也从缓存里摘掉，一并报给调用方，
+                                    // 这样正在运行的 NotifyViewer 列表也能把它摘掉，而不用等重启重新加载缓存。
+                                    match store
+                                        .lock()
+                                        .await
+                                        .drop_notifications_for_target(target.oid, target.rpid)
+                                    {
+                                        Ok(dropped) if !dropped.is_empty() => {
+                                            info!(
+                                                "Dropped {} cached notify(s) pointing at the deleted comment: {:?}",
+                                                dropped.len(),
+                                                dropped
+                                            );
+                                            removed_ids.extend(dropped);
+                                        }
+                                        Ok(_) => {}
+                                        Err(e) => error!(
+                                            "Failed to drop cached notifies for deleted comment: {e}"
+                                        ),
+                                    }
+                                }
+                                Err(e) => {
+                                    error!("Cascade delete of comment for notify {id} failed: {e}");
+                                }
+                            }
+                        }
+                    }
+                    Ok(removed_ids)
                 } else {
                     let e = format!("Can't remove notify. Response json: {}", json_res);
                     error!(e);
@@ -96,7 +292,36 @@ impl Notify {
         }
     }
 
-    pub async fn fetch(cl: Arc<Client>, csrf: Arc<String>) -> Result<HashMap<u64, Self>> {
+    /// 联动删除的反方向：评论在评论清理页被删除后调用这里，把指向它的 reply/at 通知
+    /// 从持久化缓存和（如果 `NotifyViewer` 正开着）当前展示的列表里一并摘掉。
+    ///
+    /// 这个方向目前只实现到这一步：函数本身可用、测过（见下面的单测），但还没有任何
+    /// 调用方——评论清理页不在这份快照里，这个系列也没有新建它。换句话说，双向联动的
+    /// "评论→通知"这一半没有完整交付，只是把可以单独落地、单独测试的部分先做掉，
+    /// 调用方需要在评论清理页实际删除评论成功之后接上这一个函数。
+    pub async fn forget_for_removed_comment(
+        store: &Arc<Mutex<Store>>,
+        live: Option<&Arc<Mutex<HashMap<u64, Notify>>>>,
+        oid: u64,
+        rpid: u64,
+    ) -> Result<Vec<u64>> {
+        let dropped = store.lock().await.drop_notifications_for_target(oid, rpid)?;
+        if let Some(live) = live {
+            if !dropped.is_empty() {
+                let mut guard = live.lock().await;
+                for id in &dropped {
+                    guard.remove(id);
+                }
+            }
+        }
+        Ok(dropped)
+    }
+
+    pub async fn fetch(
+        cl: Arc<Client>,
+        csrf: Arc<String>,
+        store: Arc<Mutex<Store>>,
+    ) -> Result<HashMap<u64, Self>> {
         let a = try_join!(
             Self::fetch_liked_notify(cl.clone()),
             Self::fetch_ated_notify(cl.clone()),
@@ -104,149 +329,76 @@ impl Notify {
             Self::fetch_system_notify(cl.clone(), csrf.clone())
         )?;
         let (m1, m2, m3, m4) = a;
-        Ok(m1.into_iter().chain(m2).chain(m3).chain(m4).collect())
+        let h: HashMap<u64, Self> = m1.into_iter().chain(m2).chain(m3).chain(m4).collect();
+        store.lock().await.save_notifications(&h)?;
+        Ok(h)
     }
     #[instrument(skip_all)]
     pub async fn fetch_liked_notify(cl: Arc<Client>) -> Result<HashMap<u64, Self>> {
-        let mut h: HashMap<u64, Self> = HashMap::new();
-        let mut queryid = None;
-        let mut last_time = None;
-
-        loop {
-            let json: serde_json::Value;
-            let notifys: &serde_json::Value;
-            // first get
-            if queryid.is_none() && last_time.is_none() {
-                json = get_json(
-                    cl.clone(),
-                    "https://api.bilibili.com/x/msgfeed/like?platform=web&build=0&mobi_app=web",
-                )
-                .await?;
-                notifys = &json["data"]["total"]["items"];
-                if notifys.as_array().unwrap().is_empty() {
-                    let i = "没有收到赞的通知。";
-                    info!(i);
-                    return Err(i.into());
-                }
-                last_time = notifys.as_array().unwrap().last().unwrap()["like_time"].as_u64();
-                queryid = json["data"]["total"]["cursor"]["id"].as_u64();
-            } else {
-                json=get_json(cl.clone(), format!("https://api.bilibili.com/x/msgfeed/like?platform=web&build=0&mobi_app=web&id={}&like_time={}",&queryid.unwrap().to_string(),&last_time.unwrap().to_string())).await?;
-                notifys = &json["data"]["total"]["items"];
-                last_time = notifys.as_array().unwrap().last().unwrap()["like_time"].as_u64();
-                queryid = json["data"]["total"]["cursor"]["id"].as_u64();
-            }
-
-            for i in notifys.as_array().unwrap() {
-                let notify_id = i["id"].as_u64().unwrap();
-                h.insert(notify_id, Notify::new(0));
-                info!("Fetched notify {notify_id}");
-            }
-
-            if json["data"]["total"]["cursor"]["is_end"].as_bool().unwrap() {
-                info!("收到赞的通知处理完毕。通知数量：{}", h.len());
-                break;
-            }
-        }
-        Ok(h)
+        Self::fetch_paginated(cl, NotifySource::LIKE).await
     }
 
     #[instrument(skip_all)]
     pub async fn fetch_replyed_notify(cl: Arc<Client>) -> Result<HashMap<u64, Self>> {
-        let mut h: HashMap<u64, Self> = HashMap::new();
-        let mut queryid = None;
-        let mut last_time = None;
-
-        loop {
-            let json: serde_json::Value;
-            let notifys: &serde_json::Value;
-            // first get
-            if queryid.is_none() && last_time.is_none() {
-                json = get_json(
-                    cl.clone(),
-                    "https://api.bilibili.com/x/msgfeed/reply?platform=web&build=0&mobi_app=web",
-                )
-                .await?;
-                notifys = &json["data"]["items"];
-                if notifys.as_array().unwrap().is_empty() {
-                    let i = "没有收到评论的通知。";
-                    info!(i);
-                    return Err(i.into());
-                }
-                last_time = notifys.as_array().unwrap().last().unwrap()["reply_time"].as_u64();
-                queryid = json["data"]["cursor"]["id"].as_u64();
-            } else {
-                let mut url = Url::parse(
-                    "https://api.bilibili.com/x/msgfeed/reply?platform=web&build=0&mobi_app=web",
-                )
-                .unwrap();
-                url.query_pairs_mut()
-                    .append_pair("id", &queryid.unwrap().to_string())
-                    .append_pair("reply_time", &last_time.unwrap().to_string());
-                json = get_json(cl.clone(), url).await?;
-                notifys = &json["data"]["items"];
-                last_time = notifys.as_array().unwrap().last().unwrap()["reply_time"].as_u64();
-                queryid = json["data"]["cursor"]["id"].as_u64();
-            }
-
-            for i in notifys.as_array().unwrap() {
-                let notify_id = i["id"].as_u64().unwrap();
-                h.insert(notify_id, Notify::new(1));
-                info!("Fetched notify {notify_id}");
-            }
-
-            if json["data"]["cursor"]["is_end"].as_bool().unwrap() {
-                info!("收到评论的通知处理完毕。通知数量：{}", h.len());
-                break;
-            }
-        }
-        Ok(h)
+        Self::fetch_paginated(cl, NotifySource::REPLY).await
     }
+
     #[instrument(skip_all)]
     pub async fn fetch_ated_notify(cl: Arc<Client>) -> Result<HashMap<u64, Self>> {
+        Self::fetch_paginated(cl, NotifySource::AT).await
+    }
+
+    /// 赞/评论/At 三个 feed 共用的 cursor 分页驱动，只有 `NotifySource` 描述的那几处不同。
+    #[instrument(skip_all, fields(tp = source.tp))]
+    async fn fetch_paginated(cl: Arc<Client>, source: NotifySource) -> Result<HashMap<u64, Self>> {
         let mut h: HashMap<u64, Self> = HashMap::new();
-        let mut queryid = None;
-        let mut last_time = None;
+        let mut queryid: Option<u64> = None;
+        let mut last_time: Option<u64> = None;
 
         loop {
-            let json: serde_json::Value;
-            let notifys: &serde_json::Value;
-            // first get
-            if queryid.is_none() && last_time.is_none() {
-                json = get_json(
-                    cl.clone(),
-                    "https://api.bilibili.com/x/msgfeed/at?build=0&mobi_app=web",
-                )
-                .await?;
-                notifys = &json["data"]["items"];
-                if notifys.as_array().unwrap().is_empty() {
-                    let i = "没有被At的通知。";
-                    info!(i);
-                    return Err(i.into());
+            let json = match (queryid, last_time) {
+                (Some(id), Some(t)) => {
+                    let mut url = Url::parse(source.first_url).unwrap();
+                    url.query_pairs_mut()
+                        .append_pair("id", &id.to_string())
+                        .append_pair(source.time_key, &t.to_string());
+                    get_json(cl.clone(), url).await?
                 }
-                last_time = notifys.as_array().unwrap().last().unwrap()["at_time"].as_u64();
-                queryid = json["data"]["cursor"]["id"].as_u64();
-            } else {
-                let mut url =
-                    Url::parse("https://api.bilibili.com/x/msgfeed/at?build=0&mobi_app=web")
-                        .unwrap();
-                url.query_pairs_mut()
-                    .append_pair("id", &queryid.unwrap().to_string())
-                    .append_pair("at_time", &last_time.unwrap().to_string());
-                json = get_json(cl.clone(), url).await?;
-                notifys = &json["data"]["items"];
-                last_time = notifys.as_array().unwrap().last().unwrap()["at_time"].as_u64();
-                queryid = json["data"]["cursor"]["id"].as_u64();
+                _ => get_json(cl.clone(), source.first_url).await?,
+            };
+
+            let base = (source.base_pointer)(&json);
+            let items = base["items"]
+                .as_array()
+                .ok_or("Fetch notify: items is not an array")?;
+            if queryid.is_none() && items.is_empty() {
+                info!(source.empty_message);
+                return Err(source.empty_message.into());
             }
 
-            for i in notifys.as_array().unwrap() {
-                let notify_id = i["id"].as_u64().unwrap();
-                h.insert(notify_id, Notify::new(2));
+            let last_item = items
+                .last()
+                .ok_or("Fetch notify: items is empty on a non-first page")?;
+            last_time = last_item[source.time_key].as_u64();
+            queryid = base["cursor"]["id"].as_u64();
+
+            for i in items {
+                let notify_id = i["id"]
+                    .as_u64()
+                    .ok_or("Fetch notify: item id is missing or not a number")?;
+                let target = source.target_extractor.and_then(|f| f(i));
+                h.insert(
+                    notify_id,
+                    Notify::new(source.tp, extract_content(i), target),
+                );
                 info!("Fetched notify {notify_id}");
             }
 
-            if json["data"]["cursor"]["is_end"].as_bool().unwrap() {
-                info!("被At的通知处理完毕。通知数量：{}", h.len());
+            let is_end = base["cursor"]["is_end"]
+                .as_bool()
+                .ok_or("Fetch notify: cursor.is_end is missing or not a boolean")?;
+            if is_end {
+                info!("{}。通知数量：{}", source.done_message, h.len());
                 break;
             }
         }
@@ -300,10 +452,156 @@ impl Notify {
             for i in notifys.as_array().unwrap() {
                 let notify_id = i["id"].as_u64().unwrap();
                 let notify_type = i["type"].as_u64().unwrap() as u8;
-                h.insert(notify_id, Notify::new_system_notify(notify_type, api_type));
+                h.insert(
+                    notify_id,
+                    Notify::new_system_notify(notify_type, api_type, extract_system_content(i)),
+                );
                 info!("Fetched notify {notify_id}");
             }
         }
         Ok(h)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 形状贴近 `msgfeed/reply` 实际返回的一条 item：评论坐标在 `item.oid`/`item.type`/`item.rpid`。
+    fn reply_item_json() -> Value {
+        json!({
+            "id": 123456,
+            "tp": 1,
+            "item": {
+                "business": "reply",
+                "business_id": 1,
+                "title": "",
+                "source_content": "回复了你的评论：好看",
+                "oid": 78910,
+                "type": 1,
+                "rpid": 11121314,
+            },
+            "reply_time": 1_700_000_000_u64,
+        })
+    }
+
+    #[test]
+    fn extract_comment_target_reads_oid_type_rpid() {
+        let target = extract_comment_target(&reply_item_json()).unwrap();
+        assert_eq!(target.oid, 78910);
+        assert_eq!(target.type_, 1);
+        assert_eq!(target.rpid, 11121314);
+    }
+
+    #[test]
+    fn extract_comment_target_falls_back_to_natural_type() {
+        let mut item = reply_item_json();
+        item["item"].as_object_mut().unwrap().remove("type");
+        item["item"]["natural_type"] = json!(2);
+        let target = extract_comment_target(&item).unwrap();
+        assert_eq!(target.type_, 2);
+    }
+
+    #[test]
+    fn extract_comment_target_is_none_when_rpid_missing() {
+        let mut item = reply_item_json();
+        item["item"].as_object_mut().unwrap().remove("rpid");
+        assert!(extract_comment_target(&item).is_none());
+    }
+
+    #[test]
+    fn extract_content_falls_back_past_empty_title_to_source_content() {
+        // reply_item_json() 的 title 是空字符串，这是 reply/at 通知的常态，
+        // 不应该被当成"取到了"而停在这一步。
+        assert_eq!(extract_content(&reply_item_json()), "回复了你的评论：好看");
+    }
+
+    #[test]
+    fn extract_content_prefers_nonempty_title() {
+        let mut item = reply_item_json();
+        item["item"]["title"] = json!("点赞了你的视频");
+        assert_eq!(extract_content(&item), "点赞了你的视频");
+    }
+
+    #[test]
+    fn extract_content_falls_back_to_content_when_title_and_source_content_empty() {
+        let mut item = reply_item_json();
+        item["item"]["source_content"] = json!("");
+        item["item"]["content"] = json!("最后一道字段");
+        assert_eq!(extract_content(&item), "最后一道字段");
+    }
+
+    #[test]
+    fn extract_content_is_empty_string_when_nothing_matches() {
+        let item = json!({"item": {"title": "", "source_content": ""}});
+        assert_eq!(extract_content(&item), "");
+    }
+
+    #[test]
+    fn extract_system_content_falls_back_past_empty_title() {
+        let item = json!({"title": "", "text": "系统通知内容"});
+        assert_eq!(extract_system_content(&item), "系统通知内容");
+    }
+
+    #[tokio::test]
+    async fn forget_for_removed_comment_drops_from_store_and_live_map() {
+        let store = Arc::new(Mutex::new(Store::open(":memory:").unwrap()));
+        let target = CommentTarget {
+            oid: 1,
+            type_: 1,
+            rpid: 2,
+        };
+        let mut cached = HashMap::new();
+        cached.insert(10, Notify::new(1, "reply".to_string(), Some(target)));
+        cached.insert(11, Notify::new(2, "at".to_string(), Some(target)));
+        cached.insert(
+            12,
+            Notify::new(
+                1,
+                "unrelated".to_string(),
+                Some(CommentTarget {
+                    oid: 9,
+                    type_: 1,
+                    rpid: 9,
+                }),
+            ),
+        );
+        store.lock().await.save_notifications(&cached).unwrap();
+        let live = Arc::new(Mutex::new(cached));
+
+        let mut dropped = Notify::forget_for_removed_comment(&store, Some(&live), 1, 2)
+            .await
+            .unwrap();
+        dropped.sort();
+        assert_eq!(dropped, vec![10, 11]);
+
+        let live_guard = live.lock().await;
+        assert!(!live_guard.contains_key(&10));
+        assert!(!live_guard.contains_key(&11));
+        assert!(live_guard.contains_key(&12));
+        drop(live_guard);
+
+        let cached_after = store.lock().await.load_cached().unwrap();
+        assert_eq!(cached_after.len(), 1);
+        assert!(cached_after.contains_key(&12));
+    }
+
+    #[tokio::test]
+    async fn forget_for_removed_comment_without_a_live_map_only_touches_the_store() {
+        let store = Arc::new(Mutex::new(Store::open(":memory:").unwrap()));
+        let target = CommentTarget {
+            oid: 1,
+            type_: 1,
+            rpid: 2,
+        };
+        let mut cached = HashMap::new();
+        cached.insert(10, Notify::new(1, "reply".to_string(), Some(target)));
+        store.lock().await.save_notifications(&cached).unwrap();
+
+        let dropped = Notify::forget_for_removed_comment(&store, None, 1, 2)
+            .await
+            .unwrap();
+        assert_eq!(dropped, vec![10]);
+        assert!(store.lock().await.load_cached().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,141 @@
+//! `crate::main`: the module `NotifyViewer::update` (and other screens) hand their `Action`s
+//! to. The crate entry point, login flow, window setup and other screens aren't part of this
+//! tree snapshot -- this file only reconstructs the part of the contract this series touches:
+//! driving `Action::DeleteNotify` through `delete_loop::run_delete_loop` and feeding the
+//! resulting `NvMsg`s back into `NotifyViewer`.
+
+use crate::delete_loop::run_delete_loop;
+use crate::http::backoff::Backoff;
+use crate::http::notify::Notify;
+use crate::screens::main::notify_viewer::{NotifyViewer, NvMsg};
+use crate::storage::Store;
+use crate::types::ChannelMsg;
+use iced::Task;
+use reqwest::Client;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// 各屏幕 `update` 交回给主循环的动作。大多数分支只是转发一个 `Task`；批量删除
+/// 这种需要跨屏幕共享状态（HTTP client、csrf、停止信号）的操作统一在这里处理。
+pub enum Action {
+    None,
+    Run(Task<Message>),
+    RetryFetchNotify,
+    SendtoChannel(ChannelMsg),
+    DeleteNotify {
+        notify: Arc<Mutex<HashMap<u64, Notify>>>,
+        sleep_seconds: f32,
+        store: Option<Arc<Mutex<Store>>>,
+        backoff: Arc<Mutex<Backoff>>,
+        cascade_delete: bool,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub enum Message {
+    RefreshUI(()),
+    NotifyViewer(NvMsg),
+    /// `Action::DeleteNotify` 跑完（或被停止）之后，把整轮要喂回 `NotifyViewer` 的
+    /// `NvMsg` 序列一次性带回来，由 `App::update` 按顺序转发。
+    DeleteLoopFinished(Vec<NvMsg>),
+}
+
+/// 批量删除循环用到的跨屏幕共享状态。
+pub struct App {
+    client: Arc<Client>,
+    csrf: Arc<String>,
+    notify_viewer: NotifyViewer,
+    /// `NvMsg::StopDeleteNotify` -> `Action::SendtoChannel(ChannelMsg::StopDeleteComment)`
+    /// 到达时置位，`run_delete_loop` 下一次检查时提前收尾。
+    stop_delete: Arc<AtomicBool>,
+}
+
+impl App {
+    pub fn new(client: Arc<Client>, csrf: Arc<String>) -> Self {
+        App {
+            client,
+            csrf,
+            notify_viewer: NotifyViewer::new(),
+            stop_delete: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn update(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::RefreshUI(()) => Task::none(),
+            Message::NotifyViewer(msg) => {
+                let action = self.notify_viewer.update(msg);
+                self.perform(action)
+            }
+            Message::DeleteLoopFinished(events) => {
+                let mut tasks = Vec::with_capacity(events.len());
+                for msg in events {
+                    let action = self.notify_viewer.update(msg);
+                    tasks.push(self.perform(action));
+                }
+                Task::batch(tasks)
+            }
+        }
+    }
+
+    /// 把 `NotifyViewer::update` 交回来的 `Action` 落地成真正的副作用。其它屏幕各自的
+    /// `Action` 分支不在这个系列的改动范围内，这里只写这次改动牵涉到的几条。
+    fn perform(&mut self, action: Action) -> Task<Message> {
+        match action {
+            Action::None => Task::none(),
+            Action::Run(task) => task,
+            Action::RetryFetchNotify => {
+                let cl = self.client.clone();
+                let csrf = self.csrf.clone();
+                match self.notify_viewer.store.clone() {
+                    Some(store) => Task::perform(
+                        async move {
+                            Notify::fetch(cl, csrf, store)
+                                .await
+                                .map(|h| Arc::new(Mutex::new(h)))
+                        },
+                        |result| Message::NotifyViewer(NvMsg::NotifysFetched(result)),
+                    ),
+                    // NotifyViewer::new() 已经把打不开缓存的情况记过日志了，这里只是
+                    // 没有一个 Store 可以传给 Notify::fetch（它要求必须有缓存可写）。
+                    None => {
+                        let err: crate::types::Result<Arc<Mutex<HashMap<u64, Notify>>>> =
+                            Err("Notify cache store is unavailable".into());
+                        Task::perform(async move { err }, |result| {
+                            Message::NotifyViewer(NvMsg::NotifysFetched(result))
+                        })
+                    }
+                }
+            }
+            // 目前只有批量删除会发 ChannelMsg::StopDeleteComment；其它 ChannelMsg 变体（如果有）
+            // 属于评论清理页那边的消费逻辑，不在这个系列里。
+            Action::SendtoChannel(msg) => {
+                if matches!(msg, ChannelMsg::StopDeleteComment) {
+                    self.stop_delete.store(true, Ordering::Relaxed);
+                }
+                Task::none()
+            }
+            Action::DeleteNotify {
+                notify,
+                sleep_seconds: _,
+                store,
+                backoff,
+                cascade_delete,
+            } => {
+                self.stop_delete.store(false, Ordering::Relaxed);
+                let cl = self.client.clone();
+                let csrf = self.csrf.clone();
+                let stop = self.stop_delete.clone();
+                Task::perform(
+                    run_delete_loop(cl, csrf, notify, store, backoff, cascade_delete, stop),
+                    Message::DeleteLoopFinished,
+                )
+            }
+        }
+    }
+}
+
+/// 登录流程、窗口初始化和其它屏幕都不在这个系列的改动范围内，这里留一个占位入口。
+fn main() {}
@@ -1,11 +1,14 @@
+use crate::http::backoff::Backoff;
 use crate::http::notify::Notify;
 use crate::main;
 use crate::main::Action;
+use crate::storage::Store;
 use crate::types::{ChannelMsg, Result};
 use iced::widget::{
     button, center, checkbox, column, row, scrollable, text, text_input, tooltip, Space,
 };
 use iced::{Alignment, Element, Length, Task};
+use regex::Regex;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::Mutex;
@@ -14,6 +17,8 @@ use tracing::error;
 #[derive(Debug)]
 pub struct NotifyViewer {
     pub notify: Option<Arc<Mutex<HashMap<u64, Notify>>>>,
+    /// 本地 SQLite 缓存与删除审计日志，`None` 表示打开数据库失败（退化为无缓存模式）
+    pub store: Option<Arc<Mutex<Store>>>,
     /// 删除请求间隔
     pub sleep_seconds: String,
     /// 是否正在删除
@@ -24,6 +29,14 @@ pub struct NotifyViewer {
     /// select all | deselect all state
     pub select_state: bool,
     pub error: Option<String>,
+    /// 当前生效的删除间隔（风控退避后可能大于 `sleep_seconds`），由删除循环回报
+    pub current_delay: Option<f32>,
+    /// 按内容过滤列表用的关键词/正则
+    pub filter: String,
+    /// 是否把 `filter` 当正则解释，而不是普通子串匹配
+    pub filter_is_regex: bool,
+    /// 删除评论/At 通知时，是否联动删除其指向的评论本体
+    pub cascade_delete: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -34,20 +47,90 @@ pub enum NvMsg {
     NotifysDeselectAll,
     DeleteNotify,
     StopDeleteNotify,
-    NotifyDeleted { id: u64 },
-    AllNotifyDeleted,
+    /// `ids` 是这次删除实际摘掉的全部通知 id：通常只有被删的那条，
+    /// 但联动删除评论命中时还包含同一条评论上被一并摘掉的另一条通知
+    NotifyDeleted {
+        ids: Vec<u64>,
+    },
+    AllNotifyDeleted {
+        removed: u64,
+        failed: u64,
+    },
     NotifysFetched(Result<Arc<Mutex<HashMap<u64, Notify>>>>),
     RetryFetchNotify,
+    /// 删除循环里的退避调度器回报当前生效的间隔
+    BackoffChanged(f32),
+    FilterChanged(String),
+    FilterModeToggled(bool),
+    SelectAllMatching,
+    CascadeDeleteToggled(bool),
+}
+
+/// 编译好的过滤条件，渲染/批量选中整个列表时只编译一次正则，而不是每条通知都编译一遍。
+enum CompiledFilter {
+    Empty,
+    Substring(String),
+    Regex(Option<Regex>),
+}
+
+impl CompiledFilter {
+    fn new(filter: &str, is_regex: bool) -> Self {
+        if filter.is_empty() {
+            CompiledFilter::Empty
+        } else if is_regex {
+            CompiledFilter::Regex(Regex::new(filter).ok())
+        } else {
+            CompiledFilter::Substring(filter.to_string())
+        }
+    }
+
+    /// 正则非法时视为不匹配，而不是报错中断列表渲染。
+    fn matches(&self, content: &str) -> bool {
+        match self {
+            CompiledFilter::Empty => true,
+            CompiledFilter::Substring(f) => content.contains(f.as_str()),
+            CompiledFilter::Regex(re) => re.as_ref().is_some_and(|re| re.is_match(content)),
+        }
+    }
+}
+
+/// 判断一条通知的内容是否匹配过滤条件；一次性用途，批量场景请用 `CompiledFilter` 避免重复编译正则。
+fn matches_filter(content: &str, filter: &str, is_regex: bool) -> bool {
+    CompiledFilter::new(filter, is_regex).matches(content)
 }
 impl NotifyViewer {
     pub fn new() -> Self {
+        let store = match Store::open("notify_cache.db") {
+            Ok(s) => Some(Arc::new(Mutex::new(s))),
+            Err(e) => {
+                error!("Failed to open notify cache store: {e}");
+                None
+            }
+        };
+        // 先把上次缓存的通知摆出来，真正的抓取仍会在后台跑，跑完后用 NvMsg::NotifysFetched 覆盖它。
+        let cached = store
+            .as_ref()
+            .and_then(|s| match s.blocking_lock().load_cached() {
+                Ok(m) => Some(m),
+                Err(e) => {
+                    error!("Failed to load cached notifications: {e}");
+                    None
+                }
+            })
+            .filter(|m: &HashMap<u64, Notify>| !m.is_empty());
+
         NotifyViewer {
-            notify: None,
+            notify: cached.map(|m| Arc::new(Mutex::new(m))),
+            store,
             sleep_seconds: "3".to_string(),
             is_deleting: false,
             is_fetching: true,
             select_state: false,
             error: None,
+            current_delay: None,
+            filter: String::new(),
+            filter_is_regex: false,
+            cascade_delete: false,
         }
     }
 
@@ -63,15 +146,30 @@ impl NotifyViewer {
                 a.values().filter(|e| e.is_selected).count(),
                 a.len()
             ));
-            let cl = column(a.into_iter().map(|(id, i)| {
-                checkbox(i.content.to_string(), i.is_selected)
-                    .text_shaping(text::Shaping::Advanced)
-                    .on_toggle(move |b| NvMsg::ChangeNotifyRemoveState(id, b))
-                    .into()
-            }))
+            let compiled_filter = CompiledFilter::new(&self.filter, self.filter_is_regex);
+            let cl = column(
+                a.into_iter()
+                    .filter(|(_, i)| compiled_filter.matches(&i.content))
+                    .map(|(id, i)| {
+                        checkbox(i.content.to_string(), i.is_selected)
+                            .text_shaping(text::Shaping::Advanced)
+                            .on_toggle(move |b| NvMsg::ChangeNotifyRemoveState(id, b))
+                            .into()
+                    }),
+            )
             .padding([0, 15]);
             let comments = center(scrollable(cl).height(Length::Fill).width(Length::Fill));
 
+            let filter_row = row![
+                text_input("filter by content...", &self.filter)
+                    .on_input(NvMsg::FilterChanged)
+                    .width(Length::Fill),
+                checkbox("regex", self.filter_is_regex).on_toggle(NvMsg::FilterModeToggled),
+                button("select matching").on_press(NvMsg::SelectAllMatching),
+            ]
+            .spacing(5)
+            .align_y(Alignment::Center);
+
             let control = row![
                 if self.select_state {
                     button("select all").on_press(NvMsg::NotifysSelectAll)
@@ -94,15 +192,27 @@ impl NotifyViewer {
                         button("stop").on_press(NvMsg::StopDeleteNotify)
                     } else {
                         button("remove").on_press(NvMsg::DeleteNotify)
-                    }
+                    },
+                    checkbox("also delete comment", self.cascade_delete)
+                        .on_toggle(NvMsg::CascadeDeleteToggled),
                 ]
                 .spacing(5)
                 .align_y(Alignment::Center)
             ]
+            .push_maybe(
+                self.is_deleting
+                    .then(|| {
+                        let base = self.sleep_seconds.parse::<f32>().unwrap_or(0.0);
+                        self.current_delay
+                            .filter(|d| *d > base)
+                            .map(|d| text(format!("backing off… {d:.1}s")))
+                    })
+                    .flatten(),
+            )
             .height(Length::Shrink);
 
             center(
-                iced::widget::column![head, comments, control]
+                iced::widget::column![head, filter_row, comments, control]
                     .align_x(Alignment::Center)
                     .spacing(10),
             )
@@ -173,16 +283,38 @@ impl NotifyViewer {
             }
             NvMsg::DeleteNotify => {
                 self.is_deleting = true;
+                // 断点续删：跳过上次已经记录在删除日志里的 id，避免崩溃/StopDeleteNotify 后重新来一遍
+                if let Some(store) = &self.store {
+                    match store.blocking_lock().deleted_ids() {
+                        Ok(deleted) if !deleted.is_empty() => {
+                            self.notify
+                                .as_ref()
+                                .unwrap()
+                                .blocking_lock()
+                                .retain(|id, _| !deleted.contains(id));
+                        }
+                        Ok(_) => {}
+                        Err(e) => error!("Failed to load resume state from deletion log: {e}"),
+                    }
+                }
                 return Action::DeleteNotify {
                     notify: self.notify.as_ref().unwrap().clone(),
                     sleep_seconds: self.sleep_seconds.parse::<f32>().unwrap_or(0.0),
+                    store: self.store.clone(),
+                    backoff: Arc::new(Mutex::new(Backoff::new(
+                        self.sleep_seconds.parse::<f32>().unwrap_or(0.0),
+                    ))),
+                    cascade_delete: self.cascade_delete,
                 };
             }
-            NvMsg::NotifyDeleted { id } => {
+            NvMsg::NotifyDeleted { ids } => {
                 let a = Arc::clone(self.notify.as_ref().unwrap());
                 return Action::Run(Task::perform(
                     async move {
-                        a.lock().await.remove(&id);
+                        let mut guard = a.lock().await;
+                        for id in ids {
+                            guard.remove(&id);
+                        }
                     },
                     main::Message::RefreshUI,
                 ));
@@ -191,8 +323,9 @@ impl NotifyViewer {
                 self.sleep_seconds = v;
             }
             NvMsg::StopDeleteNotify => return Action::SendtoChannel(ChannelMsg::StopDeleteComment),
-            NvMsg::AllNotifyDeleted => {
+            NvMsg::AllNotifyDeleted { removed, failed } => {
                 self.is_deleting = false;
+                crate::system_notification::notify_batch_done(removed, failed);
             }
             NvMsg::NotifysFetched(Ok(c)) => {
                 self.is_fetching = false;
@@ -209,7 +342,62 @@ impl NotifyViewer {
                 self.is_fetching = true;
                 return Action::RetryFetchNotify;
             }
+            NvMsg::BackoffChanged(delay) => {
+                self.current_delay = Some(delay);
+            }
+            NvMsg::FilterChanged(v) => {
+                self.filter = v;
+            }
+            NvMsg::FilterModeToggled(b) => {
+                self.filter_is_regex = b;
+            }
+            NvMsg::SelectAllMatching => {
+                let a = Arc::clone(self.notify.as_ref().unwrap());
+                let compiled_filter = CompiledFilter::new(&self.filter, self.filter_is_regex);
+                return Action::Run(Task::perform(
+                    async move {
+                        a.lock().await.values_mut().for_each(|e| {
+                            if compiled_filter.matches(&e.content) {
+                                e.is_selected = true;
+                            }
+                        });
+                    },
+                    main::Message::RefreshUI,
+                ));
+            }
+            NvMsg::CascadeDeleteToggled(b) => {
+                self.cascade_delete = b;
+            }
         }
         Action::None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_filter_matches_everything() {
+        assert!(matches_filter("anything", "", false));
+        assert!(matches_filter("", "", true));
+    }
+
+    #[test]
+    fn substring_mode_is_plain_contains() {
+        assert!(matches_filter("hello world", "wor", false));
+        assert!(!matches_filter("hello world", "xyz", false));
+        assert!(!matches_filter("hello world", ".*", false)); // not treated as regex
+    }
+
+    #[test]
+    fn regex_mode_matches_pattern() {
+        assert!(matches_filter("hello123", r"\d+", true));
+        assert!(!matches_filter("hello", r"\d+", true));
+    }
+
+    #[test]
+    fn invalid_regex_does_not_match_anything() {
+        assert!(!matches_filter("hello", "(unclosed", true));
+    }
+}
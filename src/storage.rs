@@ -0,0 +1,237 @@
+use crate::http::notify::{CommentTarget, Notify};
+use crate::types::Result;
+use rusqlite::{params, Connection};
+use std::collections::{HashMap, HashSet};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::instrument;
+
+/// 本地 SQLite 缓存：保存最近一次抓取到的通知列表，并记录每一次成功/失败的删除，
+/// 这样重启后不用把四个 feed 重新翻一遍页，也能看到删除的历史记录。
+pub struct Store {
+    conn: Connection,
+}
+
+impl Store {
+    #[instrument(skip_all)]
+    pub fn open(path: &str) -> Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS notifications (
+                id INTEGER PRIMARY KEY,
+                tp INTEGER NOT NULL,
+                system_api INTEGER,
+                content TEXT NOT NULL,
+                fetched_at INTEGER NOT NULL,
+                target_oid INTEGER,
+                target_type INTEGER,
+                target_rpid INTEGER
+            );
+            CREATE TABLE IF NOT EXISTS deletions (
+                id INTEGER NOT NULL,
+                tp INTEGER NOT NULL,
+                deleted_at INTEGER NOT NULL,
+                response_code INTEGER NOT NULL
+            );",
+        )?;
+        Ok(Store { conn })
+    }
+
+    /// 用这次抓取到的结果覆盖本地缓存，供下次启动时先展示再刷新。
+    /// 整体包在一个事务里，避免抓取上百条通知的中途崩溃/断电把缓存截断成半成品。
+    pub fn save_notifications(&self, notify: &HashMap<u64, Notify>) -> Result<()> {
+        let fetched_at = now_unix();
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM notifications", [])?;
+        for (id, n) in notify {
+            let target = n.target();
+            tx.execute(
+                "INSERT OR REPLACE INTO notifications (id, tp, system_api, content, fetched_at, target_oid, target_type, target_rpid) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    id,
+                    n.tp(),
+                    n.system_notify_api(),
+                    n.content,
+                    fetched_at,
+                    target.map(|t| t.oid),
+                    target.map(|t| t.type_),
+                    target.map(|t| t.rpid),
+                ],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// 读取上次缓存的通知，在后台刷新完成前先展示给用户。
+    pub fn load_cached(&self) -> Result<HashMap<u64, Notify>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, tp, system_api, content, target_oid, target_type, target_rpid FROM notifications",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let id: u64 = row.get(0)?;
+            let tp: u8 = row.get(1)?;
+            let system_api: Option<u8> = row.get(2)?;
+            let content: String = row.get(3)?;
+            let target_oid: Option<u64> = row.get(4)?;
+            let target_type: Option<u8> = row.get(5)?;
+            let target_rpid: Option<u64> = row.get(6)?;
+            let target = match (target_oid, target_type, target_rpid) {
+                (Some(oid), Some(type_), Some(rpid)) => Some(CommentTarget { oid, type_, rpid }),
+                _ => None,
+            };
+            Ok((id, Notify::from_cached(tp, system_api, content, target)))
+        })?;
+        let mut h = HashMap::new();
+        for row in rows {
+            let (id, notify) = row?;
+            h.insert(id, notify);
+        }
+        Ok(h)
+    }
+
+    /// 某条评论被删除时，清掉缓存里所有指向它的通知 id（同一条评论可能同时挂着 reply 和 at
+    /// 两种通知），让 `NotifyViewer` 列表跟着保持一致。返回值是被清掉的通知 id，供调用方日志记录。
+    pub fn drop_notifications_for_target(&self, oid: u64, rpid: u64) -> Result<Vec<u64>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id FROM notifications WHERE target_oid = ?1 AND target_rpid = ?2")?;
+        let ids: Vec<u64> = stmt
+            .query_map(params![oid, rpid], |row| row.get::<_, u64>(0))?
+            .collect::<rusqlite::Result<_>>()?;
+        self.conn.execute(
+            "DELETE FROM notifications WHERE target_oid = ?1 AND target_rpid = ?2",
+            params![oid, rpid],
+        )?;
+        Ok(ids)
+    }
+
+    /// 记录一次删除尝试的结果（服务端返回的 code），让 UI 能区分
+    /// "服务器上已经没有了" 和 "接口拒绝了这次删除"。只有真正删除成功才把通知从缓存里摘掉，
+    /// 被限流/拒绝的尝试仍然留在 `notifications` 里，下次还能重试。
+    pub fn record_deletion(&self, id: u64, tp: u8, response_code: i64) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO deletions (id, tp, deleted_at, response_code) VALUES (?1, ?2, ?3, ?4)",
+            params![id, tp, now_unix(), response_code],
+        )?;
+        if response_code == 0 {
+            self.conn
+                .execute("DELETE FROM notifications WHERE id = ?1", params![id])?;
+        }
+        Ok(())
+    }
+
+    /// 已经记录在删除日志里、且服务端确认删除成功（`response_code = 0`）的通知 id，
+    /// 批量删除中断后用它跳过已处理的部分；被拒绝或限流的尝试不算数，下次仍会重试。
+    pub fn deleted_ids(&self) -> Result<HashSet<u64>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT DISTINCT id FROM deletions WHERE response_code = 0")?;
+        let rows = stmt.query_map([], |row| row.get::<_, u64>(0))?;
+        let mut ids = HashSet::new();
+        for row in rows {
+            ids.insert(row?);
+        }
+        Ok(ids)
+    }
+}
+
+fn now_unix() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open_in_memory() -> Store {
+        Store::open(":memory:").unwrap()
+    }
+
+    #[test]
+    fn round_trip_preserves_target() {
+        let store = open_in_memory();
+        let target = CommentTarget {
+            oid: 1,
+            type_: 1,
+            rpid: 2,
+        };
+        let mut notify = HashMap::new();
+        notify.insert(10, Notify::new(1, "hi".to_string(), Some(target)));
+        notify.insert(20, Notify::new(0, "no target".to_string(), None));
+
+        store.save_notifications(&notify).unwrap();
+        let loaded = store.load_cached().unwrap();
+
+        assert_eq!(loaded.len(), 2);
+        let with_target = loaded.get(&10).unwrap().target().unwrap();
+        assert_eq!(with_target.oid, target.oid);
+        assert_eq!(with_target.type_, target.type_);
+        assert_eq!(with_target.rpid, target.rpid);
+        assert!(loaded.get(&20).unwrap().target().is_none());
+    }
+
+    #[test]
+    fn drop_notifications_for_target_removes_all_matching_and_reports_ids() {
+        let store = open_in_memory();
+        let target = CommentTarget {
+            oid: 1,
+            type_: 1,
+            rpid: 2,
+        };
+        let mut notify = HashMap::new();
+        // reply 和 at 两种通知挂在同一条评论上
+        notify.insert(10, Notify::new(1, "reply".to_string(), Some(target)));
+        notify.insert(11, Notify::new(2, "at".to_string(), Some(target)));
+        notify.insert(
+            12,
+            Notify::new(
+                1,
+                "other comment".to_string(),
+                Some(CommentTarget {
+                    oid: 9,
+                    type_: 1,
+                    rpid: 9,
+                }),
+            ),
+        );
+        store.save_notifications(&notify).unwrap();
+
+        let mut dropped = store.drop_notifications_for_target(1, 2).unwrap();
+        dropped.sort();
+        assert_eq!(dropped, vec![10, 11]);
+
+        let remaining = store.load_cached().unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert!(remaining.contains_key(&12));
+
+        // 再删一次应该是空操作，而不是报错
+        assert!(store
+            .drop_notifications_for_target(1, 2)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn record_deletion_only_drops_on_success_and_tracks_resume_ids() {
+        let store = open_in_memory();
+        let mut notify = HashMap::new();
+        notify.insert(10, Notify::new(1, "rate limited".to_string(), None));
+        notify.insert(20, Notify::new(1, "succeeds".to_string(), None));
+        store.save_notifications(&notify).unwrap();
+
+        // 被限流/拒绝：仍留在 notifications 里供下次重试，也不计入 deleted_ids
+        store.record_deletion(10, 1, -412).unwrap();
+        let remaining = store.load_cached().unwrap();
+        assert!(remaining.contains_key(&10));
+        assert!(!store.deleted_ids().unwrap().contains(&10));
+
+        // 真正删除成功：从 notifications 摘掉，且记入 deleted_ids 供断点续删跳过
+        store.record_deletion(20, 1, 0).unwrap();
+        let remaining = store.load_cached().unwrap();
+        assert!(!remaining.contains_key(&20));
+        assert!(store.deleted_ids().unwrap().contains(&20));
+    }
+}
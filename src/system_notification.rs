@@ -0,0 +1,18 @@
+use notify_rust::Notification;
+use tracing::warn;
+
+/// 批量删除跑在后台、窗口被最小化时也能看到结果：跑完后弹一条系统通知小结。
+pub fn notify_batch_done(removed: u64, failed: u64) {
+    let body = if failed == 0 {
+        format!("Removed {removed} notifications")
+    } else {
+        format!("Removed {removed} notifications ({failed} failed)")
+    };
+    if let Err(e) = Notification::new()
+        .summary("bilibili-comment-cleaning")
+        .body(&body)
+        .show()
+    {
+        warn!("Failed to show desktop notification: {e}");
+    }
+}